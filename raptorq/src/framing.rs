@@ -0,0 +1,162 @@
+use pyo3::prelude::*;
+use pyo3::types::*;
+
+use crate::PacketError;
+
+// 4-byte big-endian length prefix, enough for packets well beyond any sane symbol size.
+const LENGTH_PREFIX_LEN: usize = 4;
+
+// An encoding packet is a handful of header bytes plus one symbol, so a frame
+// anywhere near `u16::MAX` is already generous. Anything larger is either a
+// corrupted length prefix or a peer speaking a different protocol entirely -
+// reject it instead of buffering unbounded attacker-controlled data.
+const MAX_FRAME_LEN: usize = crate::PACKET_HEADER_LEN + u16::MAX as usize;
+
+#[pyclass]
+pub struct PacketFramer;
+
+#[pymethods]
+impl PacketFramer {
+    #[new]
+    fn new(obj: &PyRawObject) {
+        obj.init(PacketFramer);
+    }
+
+    pub fn frame<'p>(&self, py: Python<'p>, packet: &PyBytes) -> PyResult<&'p PyBytes> {
+        let payload = packet.as_bytes();
+        let mut framed = Vec::with_capacity(LENGTH_PREFIX_LEN + payload.len());
+        framed.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        framed.extend_from_slice(payload);
+        Ok(PyBytes::new(py, &framed))
+    }
+}
+
+#[pyclass]
+pub struct PacketDeframer {
+    buffer: Vec<u8>,
+}
+
+#[pymethods]
+impl PacketDeframer {
+    #[new]
+    fn new(obj: &PyRawObject) {
+        obj.init(PacketDeframer { buffer: Vec::new() });
+    }
+
+    /// Buffers `chunk` and returns every packet whose frame has fully
+    /// arrived, leaving any trailing partial frame buffered for the next call.
+    pub fn feed<'p>(&mut self, py: Python<'p>, chunk: &PyBytes) -> PyResult<Vec<&'p PyBytes>> {
+        self.buffer.extend_from_slice(chunk.as_bytes());
+
+        let mut packets = Vec::new();
+        loop {
+            if self.buffer.len() < LENGTH_PREFIX_LEN {
+                break;
+            }
+            let mut length_bytes = [0u8; LENGTH_PREFIX_LEN];
+            length_bytes.copy_from_slice(&self.buffer[..LENGTH_PREFIX_LEN]);
+            let declared_len = u32::from_be_bytes(length_bytes) as usize;
+            if declared_len > MAX_FRAME_LEN {
+                return Err(PacketError::py_err(format!(
+                    "malformed frame: declared length {} exceeds the {}-byte maximum",
+                    declared_len,
+                    MAX_FRAME_LEN,
+                )));
+            }
+            let frame_len = LENGTH_PREFIX_LEN + declared_len;
+            if self.buffer.len() < frame_len {
+                break;
+            }
+
+            let payload: Vec<u8> = self.buffer.drain(..frame_len).skip(LENGTH_PREFIX_LEN).collect();
+            packets.push(PyBytes::new(py, &payload));
+        }
+        Ok(packets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_frame(payload: &[u8]) -> Vec<u8> {
+        let mut framed = Vec::with_capacity(LENGTH_PREFIX_LEN + payload.len());
+        framed.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        framed.extend_from_slice(payload);
+        framed
+    }
+
+    #[test]
+    fn feed_reassembles_frames_split_across_arbitrary_chunk_boundaries() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        let mut stream = encode_frame(b"hello");
+        stream.extend(encode_frame(b"world!"));
+        stream.extend(encode_frame(b""));
+
+        // Feed one byte at a time - the worst case for chunk boundaries.
+        let mut deframer = PacketDeframer { buffer: Vec::new() };
+        let mut received: Vec<Vec<u8>> = Vec::new();
+        for byte in &stream {
+            let chunk = PyBytes::new(py, &[*byte]);
+            for packet in deframer.feed(py, chunk).unwrap() {
+                received.push(packet.as_bytes().to_vec());
+            }
+        }
+
+        assert_eq!(
+            received,
+            vec![b"hello".to_vec(), b"world!".to_vec(), Vec::new()]
+        );
+    }
+
+    #[test]
+    fn feed_reassembles_frames_fed_in_a_single_arbitrary_chunk() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        let mut stream = encode_frame(b"hello");
+        stream.extend(encode_frame(b"world!"));
+
+        let mut deframer = PacketDeframer { buffer: Vec::new() };
+        let chunk = PyBytes::new(py, &stream);
+        let received: Vec<Vec<u8>> = deframer
+            .feed(py, chunk)
+            .unwrap()
+            .into_iter()
+            .map(|packet| packet.as_bytes().to_vec())
+            .collect();
+
+        assert_eq!(received, vec![b"hello".to_vec(), b"world!".to_vec()]);
+    }
+
+    #[test]
+    fn frame_then_feed_round_trips_the_payload() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        let framer = PacketFramer;
+        let packet = PyBytes::new(py, b"a raptorq encoding packet");
+        let framed = framer.frame(py, packet).unwrap();
+
+        let mut deframer = PacketDeframer { buffer: Vec::new() };
+        let received = deframer.feed(py, framed).unwrap();
+
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].as_bytes(), packet.as_bytes());
+    }
+
+    #[test]
+    fn feed_rejects_a_declared_length_over_the_max() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        let mut oversized_header = Vec::new();
+        oversized_header.extend_from_slice(&((MAX_FRAME_LEN as u32) + 1).to_be_bytes());
+
+        let mut deframer = PacketDeframer { buffer: Vec::new() };
+        let chunk = PyBytes::new(py, &oversized_header);
+        assert!(deframer.feed(py, chunk).is_err());
+    }
+}
@@ -1,14 +1,97 @@
 use pyo3::prelude::*;
 use pyo3::types::*;
+use pyo3::exc;
+use pyo3::create_exception;
+use pyo3::PyIterProtocol;
+use std::collections::{HashSet, VecDeque};
+
+mod framing;
+use framing::{PacketFramer, PacketDeframer};
 use raptorq::{
     Encoder as EncoderNative,
     Decoder as DecoderNative,
     SourceBlockEncoder as SourceBlockEncoderNative,
     SourceBlockDecoder as SourceBlockDecoderNative,
-    ObjectTransmissionInformation,
+    ObjectTransmissionInformation as ObjectTransmissionInformationNative,
     EncodingPacket,
 };
 
+// Decoder::dump_state/load_state rely on the native Decoder's Serialize/Deserialize
+// impls, which are only available when the raptorq crate is built with `serde_support`.
+
+create_exception!(raptorq, PacketError, exc::Exception);
+
+// FEC Payload ID: 1 byte source block number + 3 byte encoding symbol id (RFC 6330).
+const PACKET_HEADER_LEN: usize = 4;
+
+fn validate_packet(data: &[u8], symbol_size: u16) -> PyResult<()> {
+    let expected = PACKET_HEADER_LEN + symbol_size as usize;
+    if data.len() != expected {
+        return Err(PacketError::py_err(format!(
+            "malformed packet: expected {} bytes, got {}",
+            expected,
+            data.len(),
+        )));
+    }
+    Ok(())
+}
+
+// Reads the FEC Payload ID (source block number, encoding symbol id) so that
+// duplicate packets - the norm on a lossy UDP socket - can be told apart from
+// genuinely new symbols instead of just counting calls. Caller must have
+// already validated `data` is at least `PACKET_HEADER_LEN` bytes.
+fn packet_id(data: &[u8]) -> (u8, u32) {
+    let source_block_number = data[0];
+    let encoding_symbol_id = u32::from_be_bytes([0, data[1], data[2], data[3]]);
+    (source_block_number, encoding_symbol_id)
+}
+
+
+#[pyclass]
+struct ObjectTransmissionInformation {
+    oti: ObjectTransmissionInformationNative
+}
+
+#[pymethods]
+impl ObjectTransmissionInformation {
+    #[staticmethod]
+    pub fn deserialize(data: &PyBytes) -> PyResult<ObjectTransmissionInformation> {
+        let bytes = data.as_bytes();
+        if bytes.len() != 12 {
+            return Err(exc::ValueError::py_err(
+                "serialized ObjectTransmissionInformation must be 12 bytes",
+            ));
+        }
+        let mut header = [0u8; 12];
+        header.copy_from_slice(bytes);
+        let oti = ObjectTransmissionInformationNative::deserialize(&header);
+        Ok(ObjectTransmissionInformation { oti })
+    }
+
+    pub fn serialize<'p>(&self, py: Python<'p>) -> PyResult<&'p PyBytes> {
+        Ok(PyBytes::new(py, &self.oti.serialize()))
+    }
+
+    pub fn transfer_length(&self) -> PyResult<u64> {
+        Ok(self.oti.transfer_length())
+    }
+
+    pub fn symbol_size(&self) -> PyResult<u16> {
+        Ok(self.oti.symbol_size())
+    }
+
+    pub fn source_blocks(&self) -> PyResult<u8> {
+        Ok(self.oti.source_blocks())
+    }
+
+    pub fn sub_blocks(&self) -> PyResult<u16> {
+        Ok(self.oti.sub_blocks())
+    }
+
+    pub fn symbol_alignment(&self) -> PyResult<u8> {
+        Ok(self.oti.symbol_alignment())
+    }
+}
 
 #[pyclass]
 struct SourceBlockEncoder {
@@ -48,7 +131,10 @@ impl SourceBlockEncoder {
 
 #[pyclass]
 struct SourceBlockDecoder {
-    decoder: SourceBlockDecoderNative
+    decoder: SourceBlockDecoderNative,
+    symbol_size: u16,
+    received_symbol_ids: HashSet<u32>,
+    complete: bool,
 }
 
 #[pymethods]
@@ -57,14 +143,43 @@ impl SourceBlockDecoder {
     fn new(obj: &PyRawObject, source_block_id: u8, symbol_size: u16, block_length: u64) {
         let decoder = SourceBlockDecoderNative::new(source_block_id, symbol_size, block_length);
         obj.init({
-            SourceBlockDecoder { decoder }
+            SourceBlockDecoder { decoder, symbol_size, received_symbol_ids: HashSet::new(), complete: false }
         });
     }
 
     pub fn decode<'p>(&mut self, py: Python<'p>, packet: &PyBytes) -> PyResult<Option<&'p PyBytes>> {
+        validate_packet(packet.as_bytes(), self.symbol_size)?;
+        self.received_symbol_ids.insert(packet_id(packet.as_bytes()).1);
         let result = self.decoder.decode(vec![EncodingPacket::deserialize(packet.as_bytes())]);
+        self.complete = self.complete || result.is_some();
         Ok(result.map(|data| PyBytes::new(py, &data)))
     }
+
+    pub fn decode_batch<'p>(&mut self, py: Python<'p>, packets: Vec<&PyBytes>) -> PyResult<Option<&'p PyBytes>> {
+        for packet in &packets {
+            validate_packet(packet.as_bytes(), self.symbol_size)?;
+        }
+        for packet in &packets {
+            self.received_symbol_ids.insert(packet_id(packet.as_bytes()).1);
+        }
+        let packets: Vec<EncodingPacket> = packets
+            .iter()
+            .map(|packet| EncodingPacket::deserialize(packet.as_bytes()))
+            .collect();
+        let result = self.decoder.decode(packets);
+        self.complete = self.complete || result.is_some();
+        Ok(result.map(|data| PyBytes::new(py, &data)))
+    }
+
+    /// Count of distinct encoding symbol ids seen so far - duplicate packets
+    /// (the norm on a lossy UDP socket) are not counted twice.
+    pub fn received_symbols(&self) -> PyResult<u32> {
+        Ok(self.received_symbol_ids.len() as u32)
+    }
+
+    pub fn is_complete(&self) -> PyResult<bool> {
+        Ok(self.complete)
+    }
 }
 
 #[pyclass]
@@ -91,36 +206,256 @@ impl Encoder {
 
         Ok(packets)
     }
+
+    pub fn get_transmission_info(&self) -> PyResult<ObjectTransmissionInformation> {
+        Ok(ObjectTransmissionInformation { oti: self.encoder.get_config() })
+    }
+
+    /// Returns an iterator that walks the source blocks one at a time,
+    /// yielding that block's source packets followed by up to
+    /// `max_repair_packets_per_block` repair packets before moving on to the
+    /// next block, instead of materializing the whole encoded stream upfront.
+    pub fn iter_encoded_packets(&self, max_repair_packets_per_block: u32) -> PyResult<EncodedPacketsIter> {
+        Ok(EncodedPacketsIter {
+            blocks: self.encoder.get_block_encoders().into(),
+            max_repair_packets_per_block,
+            current_block: None,
+            current_source: VecDeque::new(),
+            next_repair_symbol_id: 0,
+            repair_sent: 0,
+        })
+    }
+}
+
+#[pyclass]
+struct EncodedPacketsIter {
+    blocks: VecDeque<SourceBlockEncoderNative>,
+    max_repair_packets_per_block: u32,
+    current_block: Option<SourceBlockEncoderNative>,
+    current_source: VecDeque<EncodingPacket>,
+    next_repair_symbol_id: u32,
+    repair_sent: u32,
+}
+
+// Encoding symbol ids are a 24-bit field in the FEC Payload ID (RFC 6330).
+const MAX_ENCODING_SYMBOL_ID: u32 = 0x00FF_FFFF;
+
+impl EncodedPacketsIter {
+    fn next_packet(&mut self) -> PyResult<Option<EncodingPacket>> {
+        loop {
+            if let Some(packet) = self.current_source.pop_front() {
+                return Ok(Some(packet));
+            }
+            if self.current_block.is_some() && self.repair_sent < self.max_repair_packets_per_block {
+                if self.next_repair_symbol_id > MAX_ENCODING_SYMBOL_ID {
+                    return Err(PacketError::py_err(
+                        "exhausted the 24-bit encoding symbol id space for this source block",
+                    ));
+                }
+                let block = self.current_block.as_ref().unwrap();
+                let mut packets = block.repair_packets(self.next_repair_symbol_id, 1);
+                let packet = packets.pop().ok_or_else(|| {
+                    PacketError::py_err("native encoder returned no repair packet for a valid symbol id")
+                })?;
+                self.next_repair_symbol_id += 1;
+                self.repair_sent += 1;
+                return Ok(Some(packet));
+            }
+            self.current_block = self.blocks.pop_front();
+            let block = match self.current_block.as_ref() {
+                Some(block) => block,
+                None => return Ok(None),
+            };
+            let source_packets = block.source_packets();
+            self.next_repair_symbol_id = source_packets.len() as u32;
+            self.repair_sent = 0;
+            self.current_source = source_packets.into();
+        }
+    }
+}
+
+#[pyproto]
+impl PyIterProtocol for EncodedPacketsIter {
+    fn __iter__(slf: PyRefMut<Self>) -> PyResult<Py<EncodedPacketsIter>> {
+        Ok(slf.into())
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<PyObject>> {
+        Ok(self.next_packet()?.map(|packet| PyBytes::new(py, &packet.serialize()).into()))
+    }
 }
 
 #[pyclass]
 struct Decoder {
-    decoder: DecoderNative
+    decoder: DecoderNative,
+    symbol_size: u16,
+    received_symbol_ids: HashSet<(u8, u32)>,
+    complete: bool,
 }
 
 #[pymethods]
 impl Decoder {
     #[staticmethod]
     pub fn with_defaults(transfer_length: u64, maximum_transmission_unit: u16) -> PyResult<Decoder> {
-        let config = ObjectTransmissionInformation::with_defaults(
+        let config = ObjectTransmissionInformationNative::with_defaults(
             transfer_length,
             maximum_transmission_unit,
         );
+        let symbol_size = config.symbol_size();
         let decoder = DecoderNative::new(config);
-        Ok(Decoder { decoder })
+        Ok(Decoder { decoder, symbol_size, received_symbol_ids: HashSet::new(), complete: false })
+    }
+
+    #[staticmethod]
+    pub fn with_config(oti: &ObjectTransmissionInformation) -> PyResult<Decoder> {
+        let symbol_size = oti.oti.symbol_size();
+        let decoder = DecoderNative::new(oti.oti.clone());
+        Ok(Decoder { decoder, symbol_size, received_symbol_ids: HashSet::new(), complete: false })
     }
 
     pub fn decode<'p>(&mut self, py: Python<'p>, packet: &PyBytes) -> PyResult<Option<&'p PyBytes>> {
+        validate_packet(packet.as_bytes(), self.symbol_size)?;
+        self.received_symbol_ids.insert(packet_id(packet.as_bytes()));
         let result = self.decoder.decode(EncodingPacket::deserialize(packet.as_bytes()));
+        self.complete = self.complete || result.is_some();
         Ok(result.map(|data| PyBytes::new(py, &data)))
     }
+
+    /// Validates every packet in the batch before decoding any of them, so a
+    /// malformed packet later in the batch never leaves earlier packets
+    /// already fed to the decoder and counted towards `received_symbols`.
+    pub fn decode_batch<'p>(&mut self, py: Python<'p>, packets: Vec<&PyBytes>) -> PyResult<Option<&'p PyBytes>> {
+        for packet in &packets {
+            validate_packet(packet.as_bytes(), self.symbol_size)?;
+        }
+
+        for packet in packets {
+            self.received_symbol_ids.insert(packet_id(packet.as_bytes()));
+            let result = self.decoder.decode(EncodingPacket::deserialize(packet.as_bytes()));
+            if result.is_some() {
+                self.complete = true;
+                return Ok(result.map(|data| PyBytes::new(py, &data)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Count of distinct (source block, encoding symbol id) pairs seen so
+    /// far - duplicate packets (the norm on a lossy UDP socket) are not
+    /// counted twice.
+    pub fn received_symbols(&self) -> PyResult<u32> {
+        Ok(self.received_symbol_ids.len() as u32)
+    }
+
+    pub fn is_complete(&self) -> PyResult<bool> {
+        Ok(self.complete)
+    }
+
+    /// Serializes the full decoder state - including the OTI and any already
+    /// received symbols - so a transfer can be checkpointed and later resumed
+    /// without re-requesting repair symbols that were already decoded.
+    pub fn dump_state<'p>(&self, py: Python<'p>) -> PyResult<&'p PyBytes> {
+        let state = (&self.decoder, &self.received_symbol_ids, self.complete);
+        let bytes = bincode::serialize(&state)
+            .map_err(|err| exc::RuntimeError::py_err(err.to_string()))?;
+        Ok(PyBytes::new(py, &bytes))
+    }
+
+    #[staticmethod]
+    pub fn load_state(data: &PyBytes) -> PyResult<Decoder> {
+        let (decoder, received_symbol_ids, complete): (DecoderNative, HashSet<(u8, u32)>, bool) =
+            bincode::deserialize(data.as_bytes())
+                .map_err(|err| exc::ValueError::py_err(err.to_string()))?;
+        let symbol_size = decoder.get_config().symbol_size();
+        Ok(Decoder { decoder, symbol_size, received_symbol_ids, complete })
+    }
 }
 
 #[pymodule]
-fn raptorq(_py: Python, m: &PyModule) -> PyResult<()> {
+fn raptorq(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<ObjectTransmissionInformation>()?;
     m.add_class::<SourceBlockEncoder>()?;
     m.add_class::<SourceBlockDecoder>()?;
     m.add_class::<Encoder>()?;
+    m.add_class::<EncodedPacketsIter>()?;
     m.add_class::<Decoder>()?;
+    m.add_class::<PacketFramer>()?;
+    m.add_class::<PacketDeframer>()?;
+    m.add("PacketError", py.get_type::<PacketError>())?;
     Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dump_state_round_trips_and_resumes_decoding() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        let data = b"the quick brown fox jumps over the lazy dog".to_vec();
+        let encoder = EncoderNative::with_defaults(&data, 16);
+        let oti = encoder.get_config();
+        let all_packets = encoder.get_encoded_packets(0);
+        let (first_half, second_half) = all_packets.split_at(all_packets.len() / 2);
+
+        let mut decoder = Decoder {
+            decoder: DecoderNative::new(oti.clone()),
+            symbol_size: oti.symbol_size(),
+            received_symbol_ids: HashSet::new(),
+            complete: false,
+        };
+        for packet in first_half {
+            let bytes = PyBytes::new(py, &packet.serialize());
+            decoder.decode(py, bytes).unwrap();
+        }
+        assert!(!decoder.is_complete().unwrap());
+        assert_eq!(decoder.received_symbols().unwrap(), first_half.len() as u32);
+
+        let state = decoder.dump_state(py).unwrap();
+        let mut resumed = Decoder::load_state(state).unwrap();
+        assert_eq!(resumed.received_symbols().unwrap(), first_half.len() as u32);
+        assert!(!resumed.is_complete().unwrap());
+
+        let mut reconstructed = None;
+        for packet in second_half {
+            let bytes = PyBytes::new(py, &packet.serialize());
+            if let Some(result) = resumed.decode(py, bytes).unwrap() {
+                reconstructed = Some(result.as_bytes().to_vec());
+                break;
+            }
+        }
+
+        assert_eq!(reconstructed.unwrap(), data);
+        assert!(resumed.is_complete().unwrap());
+    }
+
+    #[test]
+    fn dump_state_does_not_double_count_symbols_already_seen_before_checkpointing() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        let data = b"state checkpointing must not double count duplicates".to_vec();
+        let encoder = EncoderNative::with_defaults(&data, 16);
+        let oti = encoder.get_config();
+        let packet = &encoder.get_encoded_packets(0)[0];
+
+        let mut decoder = Decoder {
+            decoder: DecoderNative::new(oti.clone()),
+            symbol_size: oti.symbol_size(),
+            received_symbol_ids: HashSet::new(),
+            complete: false,
+        };
+        let bytes = PyBytes::new(py, &packet.serialize());
+        decoder.decode(py, bytes).unwrap();
+
+        let state = decoder.dump_state(py).unwrap();
+        let mut resumed = Decoder::load_state(state).unwrap();
+
+        let bytes = PyBytes::new(py, &packet.serialize());
+        resumed.decode(py, bytes).unwrap();
+
+        assert_eq!(resumed.received_symbols().unwrap(), 1);
+    }
 }
\ No newline at end of file